@@ -0,0 +1,216 @@
+//! Decomposes a witnessed value into byte limbs and range-checks each limb
+//! against a fixed `[0, 255]` lookup table, so a 20-byte Ethereum address
+//! extracted from a keccak digest is provably well-formed rather than an
+//! unconstrained field element (as `from_raw_bytes_unchecked` would allow).
+
+use halo2_curves::{bn256::Fr, ff::Field};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+/// Number of byte limbs an Ethereum address decomposes into.
+pub const ADDRESS_LIMBS: usize = 20;
+
+#[derive(Clone, Debug)]
+pub struct DecomposeConfig {
+    limb: Column<Advice>,
+    running_sum: Column<Advice>,
+    /// `256^i` for the limb at row `i`, so the running-sum gate can place
+    /// each byte at its correct position without a private witness for the
+    /// power itself.
+    scale: Column<Fixed>,
+    /// Fixed column with `enable_constant` set, used to pin
+    /// `running_sum[0]` to a constant zero so the accumulator can't be
+    /// given a free additive offset by the prover.
+    constants: Column<Fixed>,
+    byte_table: TableColumn,
+    q_lookup: Selector,
+    q_running_sum: Selector,
+}
+
+pub struct DecomposeChip {
+    config: DecomposeConfig,
+}
+
+impl DecomposeChip {
+    pub fn construct(config: DecomposeConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fr>,
+        limb: Column<Advice>,
+        running_sum: Column<Advice>,
+        scale: Column<Fixed>,
+    ) -> DecomposeConfig {
+        meta.enable_equality(limb);
+        meta.enable_equality(running_sum);
+
+        let constants = meta.fixed_column();
+        meta.enable_constant(constants);
+
+        let byte_table = meta.lookup_table_column();
+        let q_lookup = meta.complex_selector();
+        meta.lookup("byte range check", |meta| {
+            let q_lookup = meta.query_selector(q_lookup);
+            let limb = meta.query_advice(limb, Rotation::cur());
+            vec![(q_lookup * limb, byte_table)]
+        });
+
+        let q_running_sum = meta.selector();
+        meta.create_gate("running sum", |meta| {
+            let q = meta.query_selector(q_running_sum);
+            let limb = meta.query_advice(limb, Rotation::cur());
+            let scale = meta.query_fixed(scale, Rotation::cur());
+            let sum_cur = meta.query_advice(running_sum, Rotation::cur());
+            let sum_next = meta.query_advice(running_sum, Rotation::next());
+
+            Constraints::with_selector(q, vec![sum_next - sum_cur - limb * scale])
+        });
+
+        DecomposeConfig {
+            limb,
+            running_sum,
+            scale,
+            constants,
+            byte_table,
+            q_lookup,
+            q_running_sum,
+        }
+    }
+
+    pub fn load_byte_table(&self, layouter: &mut impl Layouter<Fr>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "byte range check table",
+            |mut table| {
+                for byte in 0u64..256 {
+                    table.assign_cell(|| "byte", self.config.byte_table, byte as usize, || Value::known(Fr::from(byte)))?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Copies in `bytes` (big-endian limbs, already assigned elsewhere — e.g.
+    /// the low 20 bytes of a keccak digest), range-checks each against the
+    /// byte lookup table, accumulates `sum(limb_i * 256^i)` in a running-sum
+    /// column, and returns the final accumulated cell: the address as a
+    /// single field element that is provably bounded to 160 bits.
+    pub fn decompose_and_range_check(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        bytes: &[AssignedCell<Fr, Fr>; ADDRESS_LIMBS],
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        layouter.assign_region(
+            || "decompose address",
+            |mut region| {
+                let mut sum = region.assign_advice_from_constant(
+                    || "running_sum[0]",
+                    self.config.running_sum,
+                    0,
+                    Fr::zero(),
+                )?;
+
+                for (i, byte) in bytes.iter().rev().enumerate() {
+                    self.config.q_lookup.enable(&mut region, i)?;
+                    self.config.q_running_sum.enable(&mut region, i)?;
+
+                    byte.copy_advice(|| "limb", &mut region, self.config.limb, i)?;
+                    let scale = Fr::from(256u64).pow([i as u64]);
+                    region.assign_fixed(|| "scale", self.config.scale, i, || Value::known(scale))?;
+
+                    let next = sum.value().copied() + byte.value().copied() * Value::known(scale);
+                    sum = region.assign_advice(|| "running_sum[i+1]", self.config.running_sum, i + 1, || next)?;
+                }
+
+                Ok(sum)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! `decompose_and_range_check`'s input bytes always come from a real
+    //! keccak digest in the wallet circuits, which can never produce a
+    //! value outside `[0, 255]` — so there's no way to drive an
+    //! out-of-range limb through the public circuit surface tested in
+    //! `main.rs`. Exercise the chip directly instead, with a harness that
+    //! witnesses raw bytes.
+
+    use super::{DecomposeChip, DecomposeConfig, ADDRESS_LIMBS};
+    use halo2_curves::{bn256::Fr, ff::Field};
+    use halo2_proofs::{
+        circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    #[derive(Clone)]
+    struct DecomposeHarness {
+        bytes: [Value<Fr>; ADDRESS_LIMBS],
+    }
+
+    impl Circuit<Fr> for DecomposeHarness {
+        type Config = (Column<Advice>, DecomposeConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                bytes: [Value::unknown(); ADDRESS_LIMBS],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let witness = meta.advice_column();
+            meta.enable_equality(witness);
+            let limb = meta.advice_column();
+            let running_sum = meta.advice_column();
+            let scale = meta.fixed_column();
+            (witness, DecomposeChip::configure(meta, limb, running_sum, scale))
+        }
+
+        fn synthesize(&self, (witness, config): Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+            let chip = DecomposeChip::construct(config);
+            chip.load_byte_table(&mut layouter)?;
+
+            let bytes: Vec<AssignedCell<Fr, Fr>> = layouter.assign_region(
+                || "witness bytes",
+                |mut region| {
+                    self.bytes
+                        .iter()
+                        .enumerate()
+                        .map(|(i, byte)| region.assign_advice(|| "byte", witness, i, || *byte))
+                        .collect()
+                },
+            )?;
+            let bytes: [AssignedCell<Fr, Fr>; ADDRESS_LIMBS] = bytes.try_into().unwrap();
+
+            chip.decompose_and_range_check(layouter.namespace(|| "decompose"), &bytes)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn accepts_in_range_limbs() {
+        let k = 10;
+        let bytes = std::array::from_fn(|i| Value::known(Fr::from(i as u64)));
+
+        let circuit = DecomposeHarness { bytes };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_out_of_range_limb() {
+        let k = 10;
+        let mut bytes = [Value::known(Fr::zero()); ADDRESS_LIMBS];
+        bytes[0] = Value::known(Fr::from(256u64));
+
+        let circuit = DecomposeHarness { bytes };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
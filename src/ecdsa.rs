@@ -0,0 +1,176 @@
+//! In-circuit secp256k1 ECDSA verification and Ethereum address derivation.
+//!
+//! Binds `WalletCirciut`'s proof to a concrete `(public_key, signature)`
+//! witness instead of a bare address: the chip verifies the signature over
+//! `message_hash`, derives the 20-byte address from the public key (keccak256
+//! of the uncompressed key, low 20 bytes), and hands back the assigned
+//! address cell so the caller can constrain it against the `instance` column.
+
+use ecc::integer::rns::Range;
+use ecc::integer::IntegerInstructions;
+use ecc::maingate::{MainGate, MainGateConfig, RangeChip, RangeConfig, RangeInstructions, RegionCtx};
+use ecc::{EccConfig, GeneralEccChip};
+use ecdsa::ecdsa::{AssignedEcdsaSig, AssignedPublicKey, EcdsaChip};
+use halo2_curves::{
+    bn256::Fr,
+    secp256k1::{Fq as SecpScalar, Secp256k1Affine},
+};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed},
+};
+use keccak256::circuit::{KeccakChip, KeccakConfig};
+
+use crate::decompose::{DecomposeChip, DecomposeConfig, ADDRESS_LIMBS};
+
+/// Number of limbs / bits per limb used by the `halo2wrong` integer chip to
+/// represent secp256k1 base/scalar field elements as non-native values over
+/// `Fr`.
+const NUMBER_OF_LIMBS: usize = 4;
+const BIT_LEN_LIMB: usize = 68;
+
+/// Window size for `GeneralEccChip`'s fixed-base/variable-base scalar
+/// multiplication tables, used by [`WalletEcdsaChip::assign_aux`].
+const WINDOW_SIZE: usize = 2;
+
+#[derive(Clone, Debug)]
+pub struct WalletEcdsaConfig {
+    main_gate_config: MainGateConfig,
+    range_config: RangeConfig,
+    keccak: KeccakConfig,
+    decompose: DecomposeConfig,
+}
+
+pub struct WalletEcdsaChip {
+    config: WalletEcdsaConfig,
+    ecc_chip: GeneralEccChip<Secp256k1Affine, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+}
+
+impl WalletEcdsaChip {
+    pub fn construct(config: WalletEcdsaConfig) -> Self {
+        let ecc_chip = GeneralEccChip::<Secp256k1Affine, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::new(EccConfig::new(
+            config.range_config.clone(),
+            config.main_gate_config.clone(),
+        ));
+        Self { config, ecc_chip }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fr>,
+        limb: Column<Advice>,
+        running_sum: Column<Advice>,
+        scale: Column<Fixed>,
+    ) -> WalletEcdsaConfig {
+        let main_gate_config = MainGate::<Fr>::configure(meta);
+
+        // `GeneralEccChip` needs range tables wide enough to cover overflow
+        // for both the curve's base field (point coordinates) and its scalar
+        // field (signature components, message hash).
+        let (rns_base, rns_scalar) =
+            GeneralEccChip::<Secp256k1Affine, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::rns();
+        let mut overflow_bit_lens = rns_base.overflow_lengths();
+        overflow_bit_lens.extend(rns_scalar.overflow_lengths());
+        let composition_bit_lens = vec![BIT_LEN_LIMB / NUMBER_OF_LIMBS];
+
+        let range_config = RangeChip::<Fr>::configure(meta, &main_gate_config, composition_bit_lens, overflow_bit_lens);
+
+        let keccak = KeccakChip::configure(meta);
+        let decompose = DecomposeChip::configure(meta, limb, running_sum, scale);
+
+        WalletEcdsaConfig {
+            main_gate_config,
+            range_config,
+            keccak,
+            decompose,
+        }
+    }
+
+    /// Assigns the windowed-scalar-multiplication auxiliary generator that
+    /// `GeneralEccChip`'s incomplete-addition formulas need to stay off the
+    /// curve's exceptional points. Must be called exactly once per synthesis,
+    /// before any call to [`Self::verify_and_derive_address`] — like
+    /// [`Self::load_byte_table`], callers looping over several wallets must
+    /// hoist this call out of the loop.
+    pub fn assign_aux(&mut self, mut layouter: impl Layouter<Fr>, aux_generator: Value<Secp256k1Affine>) -> Result<(), Error> {
+        layouter.assign_region(
+            || "assign aux values",
+            |mut region| {
+                let offset = &mut 0;
+                let ctx = &mut RegionCtx::new(&mut region, offset);
+                self.ecc_chip.assign_aux_generator(ctx, aux_generator)?;
+                self.ecc_chip.assign_aux(ctx, WINDOW_SIZE, 1)?;
+                Ok(())
+            },
+        )
+    }
+
+    /// Verifies `signature` over `message_hash` for `public_key` and returns
+    /// the assigned 20-byte address (as an `Fr` element) derived from it,
+    /// together with the assigned `message_hash` cell, projected into `Fr`
+    /// via the integer chip's native reduction (see
+    /// [`crate::secp_scalar_to_fr`], which callers must use to compute the
+    /// matching public instance value). Callers must constrain the returned
+    /// `message_hash` cell against a public instance (typically a
+    /// verifier-chosen challenge) — otherwise the prover is free to sign an
+    /// arbitrary message of their own choosing and the proof is trivially
+    /// replayable.
+    pub fn verify_and_derive_address(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        public_key: Value<Secp256k1Affine>,
+        signature: Value<(SecpScalar, SecpScalar)>,
+        message_hash: Value<SecpScalar>,
+    ) -> Result<(AssignedCell<Fr, Fr>, AssignedCell<Fr, Fr>), Error> {
+        let ecdsa_chip = EcdsaChip::new(self.ecc_chip.clone());
+        let scalar_chip = self.ecc_chip.scalar_field_chip();
+        let keccak_chip = KeccakChip::construct(self.config.keccak.clone());
+
+        // `public_key` must be assigned exactly once and the resulting
+        // `AssignedPoint` threaded into both the signature check and the
+        // keccak derivation below — assigning it twice (once per region)
+        // would let a prover verify a signature under one key while hashing
+        // an unrelated, publicly-known victim key for the address.
+        let (msg_hash, digest_bytes): (AssignedCell<Fr, Fr>, [AssignedCell<Fr, Fr>; 32]) = layouter.assign_region(
+            || "verify signature and hash public key",
+            |mut region| {
+                let offset = &mut 0;
+                let ctx = &mut RegionCtx::new(&mut region, offset);
+
+                let integer_r = scalar_chip.assign_integer(ctx, signature.map(|s| s.0).into(), Range::Remainder)?;
+                let integer_s = scalar_chip.assign_integer(ctx, signature.map(|s| s.1).into(), Range::Remainder)?;
+                let msg_hash = scalar_chip.assign_integer(ctx, message_hash.into(), Range::Remainder)?;
+
+                let sig = AssignedEcdsaSig { r: integer_r, s: integer_s };
+                let pk = self.ecc_chip.assign_point(ctx, public_key)?;
+                let assigned_pk = AssignedPublicKey { point: pk.clone() };
+                ecdsa_chip.verify(ctx, &sig, &assigned_pk, &msg_hash)?;
+
+                let pk_bytes = self.ecc_chip.to_uncompressed_bytes(ctx, &pk)?;
+                let digest_bytes = keccak_chip.keccak256(ctx, &pk_bytes)?;
+
+                Ok((msg_hash.native().clone(), digest_bytes))
+            },
+        )?;
+
+        let address_bytes: &[AssignedCell<Fr, Fr>; ADDRESS_LIMBS] =
+            digest_bytes[32 - ADDRESS_LIMBS..].try_into().unwrap();
+        let decompose_chip = DecomposeChip::construct(self.config.decompose.clone());
+        let address = decompose_chip.decompose_and_range_check(
+            layouter.namespace(|| "range-check address"),
+            address_bytes,
+        )?;
+
+        Ok((address, msg_hash))
+    }
+
+    /// Loads the fixed byte-range-check and `RangeChip` tables. Must be
+    /// called exactly once per synthesis, before any call to
+    /// [`Self::verify_and_derive_address`] — callers that invoke this chip
+    /// more than once per proof (e.g. a batch circuit looping over several
+    /// wallets) must hoist this call out of the loop.
+    pub fn load_byte_table(&self, layouter: &mut impl Layouter<Fr>) -> Result<(), Error> {
+        let mut range_chip = RangeChip::<Fr>::new(self.config.range_config.clone());
+        range_chip.load_table(layouter)?;
+        DecomposeChip::construct(self.config.decompose.clone()).load_byte_table(layouter)
+    }
+}
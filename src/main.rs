@@ -1,53 +1,56 @@
-use halo2_curves::ff::Field;
 use halo2_proofs::{
-    circuit::{Layouter, Chip, SimpleFloorPlanner, Value},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Constraints, Instance, Selector},
-    poly::Rotation,
+    circuit::{Chip, Layouter, SimpleFloorPlanner, Value},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column,
+        ConstraintSystem, Instance, ProvingKey, VerifyingKey,
+    },
+    poly::kzg::{
+        commitment::{KZGCommitmentScheme, ParamsKZG},
+        multiopen::{ProverSHPLONK, VerifierSHPLONK},
+        strategy::AccumulatorStrategy,
+    },
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer},
 };
+use halo2_curves::{
+    bn256::{Bn256, Fr, G1Affine},
+    ff::{Field, PrimeField},
+    group::Curve,
+    secp256k1::{Fq as SecpScalar, Secp256k1, Secp256k1Affine},
+    CurveAffine,
+};
+use rand_core::OsRng;
 
-use std::marker::PhantomData;
+mod decompose;
+mod ecdsa;
+mod merkle;
+mod persist;
+mod poseidon;
+use ecdsa::{WalletEcdsaChip, WalletEcdsaConfig};
+use merkle::{MerkleChip, MerkleConfig};
 
 #[derive(Debug, Clone)]
-struct WalletChip<F: Field> {
+struct WalletChip {
     config: WalletConfig,
-    _marker: PhantomData<F>,
 }
 
-
-impl<F: Field> WalletChip<F> {
-    fn construct(config: <Self as Chip<F>>::Config) -> Self {
-        Self {
-            config,
-            _marker: PhantomData,
-        }
+impl WalletChip {
+    fn construct(config: <Self as Chip<Fr>>::Config) -> Self {
+        Self { config }
     }
 
-    fn configure(
-        meta: &mut ConstraintSystem<F>,
-        advice: [Column<Advice>; 1],
-        instance: Column<Instance>,
-    ) -> WalletConfig {
+    fn configure(meta: &mut ConstraintSystem<Fr>, instance: Column<Instance>) -> WalletConfig {
         meta.enable_equality(instance);
-        for column in &advice {
-            meta.enable_equality(*column);   
-        }
-        let selector = meta.selector();
 
-        meta.create_gate("wallet_address", |meta| {
-            let s = meta.query_selector(selector);
-            let ac = meta.query_advice(advice[0], Rotation::cur());
-            Constraints::with_selector(s, vec![ac.clone() - ac])
-        });
+        let limb = meta.advice_column();
+        let running_sum = meta.advice_column();
+        let scale = meta.fixed_column();
+        let ecdsa = WalletEcdsaChip::configure(meta, limb, running_sum, scale);
 
-        WalletConfig {
-            advice,
-            selector,
-            instance,
-        }
+        WalletConfig { instance, ecdsa }
     }
 }
 
-impl<F: Field> Chip<F> for WalletChip<F> {
+impl Chip<Fr> for WalletChip {
     type Config = WalletConfig;
     type Loaded = ();
 
@@ -62,88 +65,993 @@ impl<F: Field> Chip<F> for WalletChip<F> {
 
 #[derive(Clone, Debug)]
 pub struct WalletConfig {
-    pub advice: [Column<Advice>; 1],
     pub instance: Column<Instance>,
-    pub selector: Selector,
+    pub ecdsa: WalletEcdsaConfig,
+}
+
+/// Proves ownership of `address` by witnessing a secp256k1 `(public_key,
+/// signature)` pair over `message_hash` whose derived address is constrained
+/// to equal the public `instance`. The address itself is no longer a free
+/// witness: anyone producing a valid proof must actually hold the signing
+/// key for the claimed address.
+#[derive(Clone)]
+pub struct WalletCirciut {
+    pub public_key: Value<Secp256k1Affine>,
+    pub signature: Value<(SecpScalar, SecpScalar)>,
+    pub message_hash: Value<SecpScalar>,
+    /// Randomizer for `GeneralEccChip`'s windowed scalar multiplication (see
+    /// [`ecdsa::WalletEcdsaChip::assign_aux`]). Not privacy-sensitive and not
+    /// bound to any public instance — any point works, but callers should
+    /// sample a fresh one per proof rather than reusing a fixed constant.
+    pub aux_generator: Secp256k1Affine,
 }
 
-#[derive(Default, Clone)]
-pub struct WalletCirciut<F: Field> {
-    pub wallet_address: Value<F>,
-    pub _marker: PhantomData<F>,
+impl Default for WalletCirciut {
+    fn default() -> Self {
+        Self {
+            public_key: Value::unknown(),
+            signature: Value::unknown(),
+            message_hash: Value::unknown(),
+            aux_generator: Secp256k1Affine::generator(),
+        }
+    }
 }
 
-impl<F: Field> Circuit<F> for WalletCirciut<F> {
+impl Circuit<Fr> for WalletCirciut {
     type Config = WalletConfig;
     type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let instance = meta.instance_column();
+
+        WalletChip::configure(meta, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), halo2_proofs::plonk::Error> {
+        let wallet_chip = WalletChip::construct(config);
+        let mut ecdsa_chip = WalletEcdsaChip::construct(wallet_chip.config.ecdsa.clone());
+        ecdsa_chip.assign_aux(layouter.namespace(|| "assign aux"), Value::known(self.aux_generator))?;
+        ecdsa_chip.load_byte_table(&mut layouter)?;
+
+        let (address, message_hash) = ecdsa_chip.verify_and_derive_address(
+            layouter.namespace(|| "verify ownership"),
+            self.public_key,
+            self.signature,
+            self.message_hash,
+        )?;
+
+        layouter
+            .namespace(|| "out: address")
+            .constrain_instance(address.cell(), wallet_chip.config.instance, 0)?;
+        layouter
+            .namespace(|| "out: challenge")
+            .constrain_instance(message_hash.cell(), wallet_chip.config.instance, 1)
+    }
+}
+
+/// Derives the Ethereum address for `public_key` the same way the in-circuit
+/// [`ecdsa::WalletEcdsaChip`] does: keccak256 of the 64-byte uncompressed
+/// public key (`x || y`, no SEC1 tag byte), low 20 bytes, left-padded into an
+/// `Fr` element. Used by callers to compute the public instance to pass
+/// alongside a proof.
+pub fn keccak256_address(public_key: &Secp256k1Affine) -> Fr {
+    use sha3::{Digest, Keccak256};
+
+    let coordinates = public_key.coordinates().unwrap();
+    let mut uncompressed = [0u8; 64];
+    uncompressed[..32].copy_from_slice(&coordinates.x().to_repr());
+    uncompressed[32..].copy_from_slice(&coordinates.y().to_repr());
+    uncompressed[..32].reverse();
+    uncompressed[32..].reverse();
+
+    let digest = Keccak256::digest(uncompressed);
+
+    let mut repr = [0u8; 32];
+    repr[12..].copy_from_slice(&digest[12..]);
+    repr.reverse();
+    Fr::from_repr(repr).unwrap()
+}
+
+/// Reduces a secp256k1 scalar into `Fr`'s canonical range by folding its
+/// byte representation through `Fr` arithmetic (`fr = fr * 256 + byte`,
+/// most-significant byte first) — the same byte-by-byte accumulation
+/// [`decompose::DecomposeChip`] uses for address limbs. Unlike
+/// `Fr::from_repr`, this never fails: secp256k1's scalar field has a larger
+/// order than `Fr` (`Fr`'s modulus is only ~18.9% of 2^256), so a uniformly
+/// chosen `SecpScalar` is a canonical `Fr` encoding only a small minority of
+/// the time, and `from_repr` would reject the rest.
+///
+/// The same witnessed `message_hash` cell is bound to the public instance
+/// as a native `Fr` element (see
+/// [`ecdsa::WalletEcdsaChip::verify_and_derive_address`]), so callers still
+/// need `message_hash` itself to already be below `Fr::MODULUS` for the
+/// proof to correspond to a signature over the exact challenge bound here —
+/// e.g. sample the challenge in `Fr` and lift it into `SecpScalar` via
+/// `SecpScalar::from_repr`, which always succeeds in that direction since
+/// `Fr`'s modulus is the smaller of the two.
+pub fn secp_scalar_to_fr(value: SecpScalar) -> Fr {
+    value
+        .to_repr()
+        .iter()
+        .rev()
+        .fold(Fr::zero(), |acc, byte| acc * Fr::from(256u64) + Fr::from(u64::from(*byte)))
+}
+
+impl WalletCirciut {
+    /// Runs the full `keygen_vk`/`keygen_pk`/`create_proof` flow over a KZG
+    /// commitment with a Blake2b transcript and returns the serialized proof
+    /// bytes. `params` must have been set up for exactly `k` rows — KZG
+    /// params aren't downsizable in place, so a larger universal SRS has to
+    /// be trimmed (e.g. via `ParamsKZG::trim`/`downsize`, not provided here)
+    /// before it can be passed in. The resulting proof attests that the
+    /// prover holds a signature over the verifier-chosen `message_hash`
+    /// challenge from the key that owns `address` — both are bound as public
+    /// instance values.
+    pub fn prove(
+        k: u32,
+        public_key: Secp256k1Affine,
+        signature: (SecpScalar, SecpScalar),
+        message_hash: SecpScalar,
+        address: Fr,
+        params: &ParamsKZG<Bn256>,
+    ) -> Vec<u8> {
+        assert_eq!(
+            params.k(),
+            k,
+            "params sized for k={}, but caller requested k={k}",
+            params.k()
+        );
+
+        let aux_generator = (Secp256k1::generator() * SecpScalar::random(OsRng)).to_affine();
+        let circuit = WalletCirciut {
+            public_key: Value::known(public_key),
+            signature: Value::known(signature),
+            message_hash: Value::known(message_hash),
+            aux_generator,
+        };
+        let vk = keygen_vk(params, &circuit).expect("keygen_vk should not fail");
+        let pk = keygen_pk(params, vk, &circuit).expect("keygen_pk should not fail");
+
+        Self::prove_with_pk(&pk, public_key, signature, message_hash, address, params)
+    }
+
+    /// Same as [`Self::prove`], but reuses an already-generated `pk` (e.g.
+    /// one loaded via [`persist::read_pk`]) instead of re-running
+    /// `keygen_vk`/`keygen_pk` for every proof.
+    pub fn prove_with_pk(
+        pk: &ProvingKey<G1Affine>,
+        public_key: Secp256k1Affine,
+        signature: (SecpScalar, SecpScalar),
+        message_hash: SecpScalar,
+        address: Fr,
+        params: &ParamsKZG<Bn256>,
+    ) -> Vec<u8> {
+        let aux_generator = (Secp256k1::generator() * SecpScalar::random(OsRng)).to_affine();
+        let circuit = WalletCirciut {
+            public_key: Value::known(public_key),
+            signature: Value::known(signature),
+            message_hash: Value::known(message_hash),
+            aux_generator,
+        };
+
+        let public_inputs = vec![address, secp_scalar_to_fr(message_hash)];
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<_>, _, _, _, _>(
+            params,
+            pk,
+            &[circuit],
+            &[&[&public_inputs]],
+            OsRng,
+            &mut transcript,
+        )
+        .expect("create_proof should not fail");
+        transcript.finalize()
+    }
+
+    /// Verifies a proof produced by [`WalletCirciut::prove`] against the
+    /// given public inputs and verifying key, using an [`AccumulatorStrategy`].
+    pub fn verify(
+        params: &ParamsKZG<Bn256>,
+        vk: &VerifyingKey<G1Affine>,
+        proof: &[u8],
+        public_inputs: &[Fr],
+    ) -> bool {
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof);
+        let strategy = AccumulatorStrategy::new(params);
+        verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<_>, _, _, _>(
+            params,
+            vk,
+            strategy,
+            &[&[public_inputs]],
+            &mut transcript,
+        )
+        .map(|strategy| strategy.finalize())
+        .unwrap_or(false)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MerkleWalletConfig<const DEPTH: usize> {
+    pub ecdsa: WalletEcdsaConfig,
+    pub merkle: MerkleConfig,
+    pub instance: Column<Instance>,
+}
+
+/// Proves that a signature-verified wallet address is a leaf of an
+/// allowlist Merkle tree rooted at the public `instance`, without revealing
+/// which leaf it is. Combines [`WalletEcdsaChip`] (ownership) with
+/// [`MerkleChip`] (membership). `message_hash` is bound to a second public
+/// instance cell so the signature is over a verifier-chosen challenge
+/// instead of a replayable, prover-chosen message.
+#[derive(Clone)]
+pub struct MerkleWalletCirciut<const DEPTH: usize> {
+    pub public_key: Value<Secp256k1Affine>,
+    pub signature: Value<(SecpScalar, SecpScalar)>,
+    pub message_hash: Value<SecpScalar>,
+    pub path: Vec<Value<Fr>>,
+    pub position_bits: Vec<Value<bool>>,
+    /// See [`WalletCirciut::aux_generator`].
+    pub aux_generator: Secp256k1Affine,
+}
+
+impl<const DEPTH: usize> Default for MerkleWalletCirciut<DEPTH> {
+    fn default() -> Self {
+        Self {
+            public_key: Value::unknown(),
+            signature: Value::unknown(),
+            message_hash: Value::unknown(),
+            path: vec![Value::unknown(); DEPTH],
+            position_bits: vec![Value::unknown(); DEPTH],
+            aux_generator: Secp256k1Affine::generator(),
+        }
+    }
+}
 
+impl<const DEPTH: usize> Circuit<Fr> for MerkleWalletCirciut<DEPTH> {
+    type Config = MerkleWalletConfig<DEPTH>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
 
     fn without_witnesses(&self) -> Self {
         Self::default()
     }
 
-    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        let advice = [meta.advice_column()];
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
         let instance = meta.instance_column();
-        
-        WalletChip::configure(meta, advice, instance)
+        meta.enable_equality(instance);
+
+        let limb = meta.advice_column();
+        let running_sum = meta.advice_column();
+        let scale = meta.fixed_column();
+        let ecdsa = WalletEcdsaChip::configure(meta, limb, running_sum, scale);
+
+        let merkle_advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+        let poseidon_state = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+        let poseidon_partial_sbox = meta.advice_column();
+        let poseidon_rc_a = [meta.fixed_column(), meta.fixed_column(), meta.fixed_column()];
+        let poseidon_rc_b = [meta.fixed_column(), meta.fixed_column(), meta.fixed_column()];
+        let poseidon = poseidon::WalletPoseidonChip::configure(
+            meta,
+            poseidon_state,
+            poseidon_partial_sbox,
+            poseidon_rc_a,
+            poseidon_rc_b,
+        );
+        let merkle = MerkleChip::configure(meta, merkle_advice, poseidon);
+
+        MerkleWalletConfig { ecdsa, merkle, instance }
     }
 
     fn synthesize(
         &self,
         config: Self::Config,
-        mut layouter: impl Layouter<F>,
+        mut layouter: impl Layouter<Fr>,
     ) -> Result<(), halo2_proofs::plonk::Error> {
-        let wallet_chip = WalletChip::<F>::construct(config);
+        let mut ecdsa_chip = WalletEcdsaChip::construct(config.ecdsa);
+        let merkle_chip = MerkleChip::construct(config.merkle);
+        ecdsa_chip.assign_aux(layouter.namespace(|| "assign aux"), Value::known(self.aux_generator))?;
+        ecdsa_chip.load_byte_table(&mut layouter)?;
 
-        let out = layouter.assign_region(|| "confirm_wallet", 
-            |mut region| {
-                let advice = wallet_chip.config.advice;
-                let s = wallet_chip.config.selector;
+        let (leaf, message_hash) = ecdsa_chip.verify_and_derive_address(
+            layouter.namespace(|| "verify ownership"),
+            self.public_key,
+            self.signature,
+            self.message_hash,
+        )?;
 
-                s.enable(&mut region, 0)?;
-                let wallet_address = region.assign_advice(
-                    || "address", advice[0], 0, || self.wallet_address)?;
+        let root = merkle_chip.root(
+            layouter.namespace(|| "allowlist membership"),
+            leaf,
+            &self.path,
+            &self.position_bits,
+        )?;
 
-                Ok(wallet_address)
-            },
+        layouter
+            .namespace(|| "out: root")
+            .constrain_instance(root.cell(), config.instance, 0)?;
+        layouter
+            .namespace(|| "out: challenge")
+            .constrain_instance(message_hash.cell(), config.instance, 1)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WalletCommitmentConfig {
+    pub ecdsa: WalletEcdsaConfig,
+    pub poseidon: poseidon::WalletPoseidonConfig,
+    pub blinding: Column<Advice>,
+    pub instance: Column<Instance>,
+}
+
+/// Proves ownership of a wallet address and publishes only
+/// `Poseidon(address, blinding)` as the instance, rather than the address
+/// itself. Re-authenticating with the same address from a fresh `blinding`
+/// produces an unlinkable commitment each time. `message_hash` is bound to a
+/// second public instance cell so the ownership proof is over a
+/// verifier-chosen challenge rather than a replayable, prover-chosen message.
+#[derive(Clone)]
+pub struct WalletCommitmentCirciut {
+    pub public_key: Value<Secp256k1Affine>,
+    pub signature: Value<(SecpScalar, SecpScalar)>,
+    pub message_hash: Value<SecpScalar>,
+    pub blinding: Value<Fr>,
+    /// See [`WalletCirciut::aux_generator`].
+    pub aux_generator: Secp256k1Affine,
+}
+
+impl Default for WalletCommitmentCirciut {
+    fn default() -> Self {
+        Self {
+            public_key: Value::unknown(),
+            signature: Value::unknown(),
+            message_hash: Value::unknown(),
+            blinding: Value::unknown(),
+            aux_generator: Secp256k1Affine::generator(),
+        }
+    }
+}
+
+impl Circuit<Fr> for WalletCommitmentCirciut {
+    type Config = WalletCommitmentConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        let limb = meta.advice_column();
+        let running_sum = meta.advice_column();
+        let scale = meta.fixed_column();
+        let ecdsa = WalletEcdsaChip::configure(meta, limb, running_sum, scale);
+
+        let blinding = meta.advice_column();
+        meta.enable_equality(blinding);
+
+        let poseidon_state = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+        let poseidon_partial_sbox = meta.advice_column();
+        let poseidon_rc_a = [meta.fixed_column(), meta.fixed_column(), meta.fixed_column()];
+        let poseidon_rc_b = [meta.fixed_column(), meta.fixed_column(), meta.fixed_column()];
+        let poseidon = poseidon::WalletPoseidonChip::configure(
+            meta,
+            poseidon_state,
+            poseidon_partial_sbox,
+            poseidon_rc_a,
+            poseidon_rc_b,
+        );
+
+        WalletCommitmentConfig {
+            ecdsa,
+            poseidon,
+            blinding,
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), halo2_proofs::plonk::Error> {
+        let mut ecdsa_chip = WalletEcdsaChip::construct(config.ecdsa);
+        let poseidon_chip = poseidon::WalletPoseidonChip::construct(config.poseidon);
+        ecdsa_chip.assign_aux(layouter.namespace(|| "assign aux"), Value::known(self.aux_generator))?;
+        ecdsa_chip.load_byte_table(&mut layouter)?;
+
+        let (address, message_hash) = ecdsa_chip.verify_and_derive_address(
+            layouter.namespace(|| "verify ownership"),
+            self.public_key,
+            self.signature,
+            self.message_hash,
+        )?;
+
+        let blinding = layouter.assign_region(
+            || "witness blinding",
+            |mut region| region.assign_advice(|| "blinding", config.blinding, 0, || self.blinding),
+        )?;
+
+        let commitment = poseidon_chip.hash_pair(
+            layouter.namespace(|| "commit to address"),
+            address,
+            blinding,
         )?;
+
+        layouter
+            .namespace(|| "out: commitment")
+            .constrain_instance(commitment.cell(), config.instance, 0)?;
         layouter
-            .namespace(|| "out")
-            .constrain_instance(out.cell(), wallet_chip.config.instance, 0)
+            .namespace(|| "out: challenge")
+            .constrain_instance(message_hash.cell(), config.instance, 1)
+    }
+}
+
+/// Sized at configure time via [`Circuit::Params`], so the number of
+/// addresses batched into one proof (e.g. a multisig's signer set) doesn't
+/// have to be baked into the type.
+#[cfg(feature = "circuit-params")]
+#[derive(Clone, Default, Debug)]
+pub struct BatchWalletParams {
+    pub num_addresses: usize,
+}
+
+#[cfg(feature = "circuit-params")]
+#[derive(Clone, Debug)]
+pub struct BatchWalletConfig {
+    ecdsa: WalletEcdsaConfig,
+    instance: Column<Instance>,
+}
+
+/// Proves ownership of `params().num_addresses` wallets in a single proof.
+/// Each wallet constrains two instance cells: its derived address at `2*i`
+/// and its `message_hash` challenge at `2*i + 1`, so every entry in the
+/// batch is bound to a verifier-chosen challenge rather than independently
+/// replayable from a public signature.
+#[cfg(feature = "circuit-params")]
+#[derive(Clone, Default)]
+pub struct BatchWalletCirciut {
+    pub wallets: Vec<(
+        Value<Secp256k1Affine>,
+        Value<(SecpScalar, SecpScalar)>,
+        Value<SecpScalar>,
+    )>,
+    /// See [`WalletCirciut::aux_generator`]. One generator for the whole
+    /// batch: every wallet in the loop below shares the same underlying
+    /// `GeneralEccChip`, so the aux generator only needs assigning once.
+    pub aux_generator: Secp256k1Affine,
+}
+
+#[cfg(feature = "circuit-params")]
+impl Circuit<Fr> for BatchWalletCirciut {
+    type Config = BatchWalletConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = BatchWalletParams;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            wallets: vec![Default::default(); self.wallets.len()],
+            aux_generator: self.aux_generator,
+        }
+    }
+
+    fn params(&self) -> Self::Params {
+        BatchWalletParams {
+            num_addresses: self.wallets.len(),
+        }
+    }
+
+    fn configure_with_params(meta: &mut ConstraintSystem<Fr>, params: Self::Params) -> Self::Config {
+        // `num_addresses` deliberately does not change which columns/gates
+        // get configured: every wallet reuses the exact same ecdsa/keccak/
+        // decomposition region shape at a fresh row offset (that's what
+        // `Layouter::assign_region` is for), so there is nothing to
+        // preallocate per-N at configure time. `num_addresses` instead
+        // bounds `synthesize`'s loop and the number of instance cells
+        // constrained — see the loop below.
+        let _ = params.num_addresses;
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        let limb = meta.advice_column();
+        let running_sum = meta.advice_column();
+        let scale = meta.fixed_column();
+        let ecdsa = WalletEcdsaChip::configure(meta, limb, running_sum, scale);
+
+        BatchWalletConfig { ecdsa, instance }
+    }
+
+    fn configure(_meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        unreachable!("BatchWalletCirciut requires configure_with_params")
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), halo2_proofs::plonk::Error> {
+        let mut ecdsa_chip = WalletEcdsaChip::construct(config.ecdsa);
+        ecdsa_chip.assign_aux(layouter.namespace(|| "assign aux"), Value::known(self.aux_generator))?;
+        ecdsa_chip.load_byte_table(&mut layouter)?;
+
+        for (i, (public_key, signature, message_hash)) in self.wallets.iter().enumerate() {
+            let (address, message_hash) = ecdsa_chip.verify_and_derive_address(
+                layouter.namespace(|| format!("wallet {i}")),
+                *public_key,
+                *signature,
+                *message_hash,
+            )?;
+            layouter
+                .namespace(|| format!("out {i}: address"))
+                .constrain_instance(address.cell(), config.instance, 2 * i)?;
+            layouter
+                .namespace(|| format!("out {i}: challenge"))
+                .constrain_instance(message_hash.cell(), config.instance, 2 * i + 1)?;
+        }
+
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::marker::PhantomData;
-
     use super::WalletCirciut;
-    use hex;
     use halo2_curves::{
-        serde::SerdeObject,
-        bn256::Fr
+        ff::{Field, PrimeField},
+        group::Curve,
+        secp256k1::{Fq as SecpScalar, Secp256k1, Secp256k1Affine},
     };
-    use halo2_proofs::{dev::MockProver, circuit::Value};
+    use halo2_proofs::{circuit::Value, dev::MockProver};
+    use rand_core::OsRng;
+
+    /// `secp256k1`'s base and scalar fields share the same byte width, so an
+    /// `x`-coordinate can be reinterpreted as a scalar without a full
+    /// reduction mod `n` for the purposes of this test fixture.
+    fn mod_n(x: halo2_curves::secp256k1::Fp) -> SecpScalar {
+        SecpScalar::from_repr(x.to_repr()).unwrap()
+    }
+
+    /// A fresh randomizer for `GeneralEccChip`'s windowed scalar
+    /// multiplication — see `WalletCirciut::aux_generator`.
+    fn aux_generator() -> Secp256k1Affine {
+        (Secp256k1::generator() * SecpScalar::random(OsRng)).to_affine()
+    }
+
+    /// Signs `message_hash` with a freshly generated secp256k1 key and
+    /// derives the Ethereum address (keccak256 of the uncompressed public
+    /// key, low 20 bytes) that the circuit should accept. `message_hash` is
+    /// sampled in `Fr` and lifted into `SecpScalar` (always canonical there,
+    /// since `Fr`'s modulus is smaller than secp256k1's scalar field order)
+    /// because the same witnessed cell is also bound to the public instance
+    /// as a native `Fr` element — see `secp_scalar_to_fr`.
+    fn signed_wallet() -> (Secp256k1Affine, (SecpScalar, SecpScalar), SecpScalar, super::Fr) {
+        let sk = SecpScalar::random(OsRng);
+        let public_key = (Secp256k1::generator() * sk).to_affine();
+        let message_hash = SecpScalar::from_repr(super::Fr::random(OsRng).to_repr()).unwrap();
+
+        let k = SecpScalar::random(OsRng);
+        let r = mod_n((Secp256k1::generator() * k).to_affine().x);
+        let s = k.invert().unwrap() * (message_hash + r * sk);
+
+        let address = super::keccak256_address(&public_key);
+
+        (public_key, (r, s), message_hash, address)
+    }
 
     #[test]
     fn verify() {
-        let k = 4;
-
-        let address_str = "0x880262912356F79aAc79C00C1C9c0f6ce1BDD6ad".strip_prefix("0x").unwrap();
-        let address = hex::decode(address_str).unwrap();
-        let address = Fr::from_raw_bytes_unchecked(&[vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], address].concat());
+        let k = 18;
+        let (public_key, signature, message_hash, address) = signed_wallet();
 
         let circuit = WalletCirciut {
-            wallet_address: Value::known(address), 
-            _marker: PhantomData,
+            public_key: Value::known(public_key),
+            signature: Value::known(signature),
+            message_hash: Value::known(message_hash),
+            aux_generator: aux_generator(),
         };
 
-        let public_inputs = vec![address];
+        let public_inputs = vec![address, super::secp_scalar_to_fr(message_hash)];
 
         let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
         assert_eq!(prover.verify(), Ok(()));
     }
+
+    #[test]
+    fn rejects_forged_signature() {
+        let k = 18;
+        let (public_key, (r, _), message_hash, address) = signed_wallet();
+        // Swap in an `s` that wasn't produced by the signing key — the
+        // ECDSA check inside `verify_and_derive_address` should reject it.
+        let forged_signature = (r, message_hash);
+
+        let circuit = WalletCirciut {
+            public_key: Value::known(public_key),
+            signature: Value::known(forged_signature),
+            message_hash: Value::known(message_hash),
+            aux_generator: aux_generator(),
+        };
+        let public_inputs = vec![address, super::secp_scalar_to_fr(message_hash)];
+
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn rejects_mismatched_public_key() {
+        let k = 18;
+        let (_, signature, message_hash, _) = signed_wallet();
+        let (other_public_key, _, _, other_address) = signed_wallet();
+
+        // `signature` verifies under the first key, not `other_public_key` —
+        // the proof must not be able to claim `other_address` with it.
+        let circuit = WalletCirciut {
+            public_key: Value::known(other_public_key),
+            signature: Value::known(signature),
+            message_hash: Value::known(message_hash),
+            aux_generator: aux_generator(),
+        };
+        let public_inputs = vec![other_address, super::secp_scalar_to_fr(message_hash)];
+
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn rejects_wrong_message_hash_instance() {
+        let k = 18;
+        let (public_key, signature, message_hash, address) = signed_wallet();
+        let wrong_challenge = super::secp_scalar_to_fr(message_hash) + super::Fr::one();
+
+        let circuit = WalletCirciut {
+            public_key: Value::known(public_key),
+            signature: Value::known(signature),
+            message_hash: Value::known(message_hash),
+            aux_generator: aux_generator(),
+        };
+        let public_inputs = vec![address, wrong_challenge];
+
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn prove_and_verify() {
+        use halo2_proofs::{plonk::keygen_vk, poly::kzg::commitment::ParamsKZG};
+
+        let k = 18;
+        let (public_key, signature, message_hash, address) = signed_wallet();
+
+        let params = ParamsKZG::new(k);
+        let vk = keygen_vk(
+            &params,
+            &WalletCirciut {
+                public_key: Value::known(public_key),
+                signature: Value::known(signature),
+                message_hash: Value::known(message_hash),
+                aux_generator: aux_generator(),
+            },
+        )
+        .unwrap();
+
+        let proof = WalletCirciut::prove(k, public_key, signature, message_hash, address, &params);
+        let public_inputs = [address, super::secp_scalar_to_fr(message_hash)];
+        assert!(WalletCirciut::verify(&params, &vk, &proof, &public_inputs));
+    }
+
+    #[test]
+    fn merkle_allowlist_membership() {
+        use super::MerkleWalletCirciut;
+        use halo2_gadgets::poseidon::primitives::{self as poseidon_primitives, ConstantLength, P128Pow5T3};
+
+        const DEPTH: usize = 3;
+        let k = 18;
+
+        let (public_key, signature, message_hash, address) = signed_wallet();
+
+        // Build a depth-3 tree where `address` is the leftmost leaf and every
+        // other leaf/sibling is a dummy value, mirroring how the in-circuit
+        // `MerkleChip` folds a path with Poseidon.
+        let siblings = [super::Fr::from(1), super::Fr::from(2), super::Fr::from(3)];
+        let position_bits = [false, false, false];
+
+        let mut digest = address;
+        for (sibling, bit) in siblings.iter().zip(position_bits.iter()) {
+            let (left, right) = if *bit { (*sibling, digest) } else { (digest, *sibling) };
+            digest = poseidon_primitives::Hash::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init()
+                .hash([left, right]);
+        }
+        let root = digest;
+
+        let circuit = MerkleWalletCirciut::<DEPTH> {
+            public_key: Value::known(public_key),
+            signature: Value::known(signature),
+            message_hash: Value::known(message_hash),
+            path: siblings.iter().map(|s| Value::known(*s)).collect(),
+            position_bits: position_bits.iter().map(|b| Value::known(*b)).collect(),
+            aux_generator: aux_generator(),
+        };
+
+        let public_inputs = vec![root, super::secp_scalar_to_fr(message_hash)];
+
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn merkle_allowlist_membership_right_child() {
+        use super::MerkleWalletCirciut;
+        use halo2_gadgets::poseidon::primitives::{self as poseidon_primitives, ConstantLength, P128Pow5T3};
+
+        const DEPTH: usize = 3;
+        let k = 18;
+
+        let (public_key, signature, message_hash, address) = signed_wallet();
+
+        // Same tree shape as `merkle_allowlist_membership`, but with the
+        // middle-level bit set to `true` so `address` sits as the *right*
+        // child at that level — exercises `cond_swap`'s `bit = 1` branch,
+        // which the all-`false` path above never touches.
+        let siblings = [super::Fr::from(1), super::Fr::from(2), super::Fr::from(3)];
+        let position_bits = [false, true, false];
+
+        let mut digest = address;
+        for (sibling, bit) in siblings.iter().zip(position_bits.iter()) {
+            let (left, right) = if *bit { (*sibling, digest) } else { (digest, *sibling) };
+            digest = poseidon_primitives::Hash::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init()
+                .hash([left, right]);
+        }
+        let root = digest;
+
+        let circuit = MerkleWalletCirciut::<DEPTH> {
+            public_key: Value::known(public_key),
+            signature: Value::known(signature),
+            message_hash: Value::known(message_hash),
+            path: siblings.iter().map(|s| Value::known(*s)).collect(),
+            position_bits: position_bits.iter().map(|b| Value::known(*b)).collect(),
+            aux_generator: aux_generator(),
+        };
+
+        let public_inputs = vec![root, super::secp_scalar_to_fr(message_hash)];
+
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn merkle_rejects_non_member_leaf() {
+        use super::MerkleWalletCirciut;
+        use halo2_gadgets::poseidon::primitives::{self as poseidon_primitives, ConstantLength, P128Pow5T3};
+
+        const DEPTH: usize = 3;
+        let k = 18;
+
+        // Build the tree for `member_address`, but sign with an unrelated
+        // wallet: `non_member_address`'s signature is valid, but it was
+        // never folded into this tree, so the re-derived root must not
+        // match `root`.
+        let (_, _, _, member_address) = signed_wallet();
+        let (public_key, signature, message_hash, non_member_address) = signed_wallet();
+        assert_ne!(member_address, non_member_address);
+
+        let siblings = [super::Fr::from(1), super::Fr::from(2), super::Fr::from(3)];
+        let position_bits = [false, false, false];
+
+        let mut digest = member_address;
+        for (sibling, bit) in siblings.iter().zip(position_bits.iter()) {
+            let (left, right) = if *bit { (*sibling, digest) } else { (digest, *sibling) };
+            digest = poseidon_primitives::Hash::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init()
+                .hash([left, right]);
+        }
+        let root = digest;
+
+        let circuit = MerkleWalletCirciut::<DEPTH> {
+            public_key: Value::known(public_key),
+            signature: Value::known(signature),
+            message_hash: Value::known(message_hash),
+            path: siblings.iter().map(|s| Value::known(*s)).collect(),
+            position_bits: position_bits.iter().map(|b| Value::known(*b)).collect(),
+            aux_generator: aux_generator(),
+        };
+
+        let public_inputs = vec![root, super::secp_scalar_to_fr(message_hash)];
+
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn poseidon_commitment() {
+        use super::WalletCommitmentCirciut;
+        use halo2_gadgets::poseidon::primitives::{ConstantLength, Hash as PoseidonHash, P128Pow5T3};
+
+        let k = 18;
+        let (public_key, signature, message_hash, address) = signed_wallet();
+        let blinding = super::Fr::from(42);
+
+        let commitment = PoseidonHash::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init().hash([address, blinding]);
+
+        let circuit = WalletCommitmentCirciut {
+            public_key: Value::known(public_key),
+            signature: Value::known(signature),
+            message_hash: Value::known(message_hash),
+            blinding: Value::known(blinding),
+            aux_generator: aux_generator(),
+        };
+
+        let public_inputs = vec![commitment, super::secp_scalar_to_fr(message_hash)];
+
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn poseidon_commitment_rejects_wrong_blinding() {
+        use super::WalletCommitmentCirciut;
+        use halo2_gadgets::poseidon::primitives::{ConstantLength, Hash as PoseidonHash, P128Pow5T3};
+
+        let k = 18;
+        let (public_key, signature, message_hash, address) = signed_wallet();
+        let blinding = super::Fr::from(42);
+
+        // The public commitment is over the real `blinding`, but the
+        // circuit is witnessed with a different one — the re-derived
+        // Poseidon hash must not match.
+        let commitment = PoseidonHash::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init().hash([address, blinding]);
+        let wrong_blinding = blinding + super::Fr::one();
+
+        let circuit = WalletCommitmentCirciut {
+            public_key: Value::known(public_key),
+            signature: Value::known(signature),
+            message_hash: Value::known(message_hash),
+            blinding: Value::known(wrong_blinding),
+            aux_generator: aux_generator(),
+        };
+
+        let public_inputs = vec![commitment, super::secp_scalar_to_fr(message_hash)];
+
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[cfg(feature = "circuit-params")]
+    #[test]
+    fn batch_wallet_ownership() {
+        use super::BatchWalletCirciut;
+
+        let k = 18;
+        let wallets: Vec<_> = (0..3).map(|_| signed_wallet()).collect();
+        let public_inputs: Vec<_> = wallets
+            .iter()
+            .flat_map(|(_, _, message_hash, address)| [*address, super::secp_scalar_to_fr(*message_hash)])
+            .collect();
+
+        let circuit = BatchWalletCirciut {
+            wallets: wallets
+                .iter()
+                .map(|(pk, sig, hash, _)| (Value::known(*pk), Value::known(*sig), Value::known(*hash)))
+                .collect(),
+            aux_generator: aux_generator(),
+        };
+
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[cfg(feature = "circuit-params")]
+    #[test]
+    fn batch_wallet_ownership_rejects_forged_signature() {
+        use super::BatchWalletCirciut;
+
+        let k = 18;
+        let wallets: Vec<_> = (0..3).map(|_| signed_wallet()).collect();
+        let public_inputs: Vec<_> = wallets
+            .iter()
+            .flat_map(|(_, _, message_hash, address)| [*address, super::secp_scalar_to_fr(*message_hash)])
+            .collect();
+
+        // Forge the signature on the middle wallet only — the other two
+        // stay valid, so this isolates that a single bad entry in the batch
+        // is still caught rather than averaged away.
+        let mut witnessed_wallets: Vec<_> = wallets
+            .iter()
+            .map(|(pk, sig, hash, _)| (Value::known(*pk), Value::known(*sig), Value::known(*hash)))
+            .collect();
+        let (_, (r, _), message_hash, _) = wallets[1];
+        witnessed_wallets[1].1 = Value::known((r, message_hash));
+
+        let circuit = BatchWalletCirciut {
+            wallets: witnessed_wallets,
+            aux_generator: aux_generator(),
+        };
+
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn vk_round_trip() {
+        use halo2_proofs::{plonk::keygen_vk, poly::kzg::commitment::ParamsKZG};
+
+        let k = 18;
+        let params = ParamsKZG::new(k);
+        let vk = keygen_vk(&params, &WalletCirciut::default()).unwrap();
+
+        let mut bytes = Vec::new();
+        super::persist::write_vk(&vk, &mut bytes).unwrap();
+        let read_back = super::persist::read_vk(&mut bytes.as_slice()).unwrap();
+
+        let mut round_tripped = Vec::new();
+        super::persist::write_vk(&read_back, &mut round_tripped).unwrap();
+        assert_eq!(bytes, round_tripped);
+    }
+
+    #[test]
+    fn pk_round_trip() {
+        use halo2_proofs::{plonk::{keygen_pk, keygen_vk}, poly::kzg::commitment::ParamsKZG};
+
+        let k = 18;
+        let params = ParamsKZG::new(k);
+        let vk = keygen_vk(&params, &WalletCirciut::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &WalletCirciut::default()).unwrap();
+
+        let mut bytes = Vec::new();
+        super::persist::write_pk(&pk, &mut bytes).unwrap();
+        let read_back = super::persist::read_pk(&mut bytes.as_slice()).unwrap();
+
+        let mut round_tripped = Vec::new();
+        super::persist::write_pk(&read_back, &mut round_tripped).unwrap();
+        assert_eq!(bytes, round_tripped);
+    }
+
+    #[test]
+    fn prove_with_persisted_pk_round_trip() {
+        use halo2_proofs::{plonk::{keygen_pk, keygen_vk}, poly::kzg::commitment::ParamsKZG};
+
+        let k = 18;
+        let (public_key, signature, message_hash, address) = signed_wallet();
+
+        let params = ParamsKZG::new(k);
+        let vk = keygen_vk(&params, &WalletCirciut::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &WalletCirciut::default()).unwrap();
+
+        // Persist and reload the pk — the caller proving with it never runs
+        // keygen_pk at all, matching how a deployment would reuse a pk
+        // across many proving runs.
+        let mut pk_bytes = Vec::new();
+        super::persist::write_pk(&pk, &mut pk_bytes).unwrap();
+        let pk = super::persist::read_pk(&mut pk_bytes.as_slice()).unwrap();
+
+        let proof = WalletCirciut::prove_with_pk(&pk, public_key, signature, message_hash, address, &params);
+
+        let mut proof_bytes = Vec::new();
+        super::persist::write_proof(&proof, &mut proof_bytes).unwrap();
+        let proof = super::persist::read_proof(&mut proof_bytes.as_slice()).unwrap();
+
+        let public_inputs = [address, super::secp_scalar_to_fr(message_hash)];
+        assert!(WalletCirciut::verify(&params, pk.get_vk(), &proof, &public_inputs));
+    }
+
+    #[test]
+    fn circuit_cost_report() {
+        // Smoke-tests that `circuit_cost` runs to completion for the `k`
+        // used throughout these tests, without asserting on exact numbers
+        // that would drift whenever a gate is added.
+        let _cost = super::persist::circuit_cost(18);
+    }
 }
 
 
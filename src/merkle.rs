@@ -0,0 +1,144 @@
+//! Merkle allowlist membership: proves a witnessed leaf is included in a
+//! tree of allowed wallet addresses rooted at the public `instance`, without
+//! revealing which leaf. Each level conditionally swaps the running digest
+//! with its sibling (`cond_swap`, selected by a witnessed position bit) and
+//! folds the pair through [`WalletPoseidonChip`](crate::poseidon::WalletPoseidonChip).
+
+use halo2_curves::bn256::Fr;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::poseidon::{WalletPoseidonChip, WalletPoseidonConfig};
+
+#[derive(Clone, Debug)]
+pub struct MerkleConfig {
+    /// `[current, sibling, position_bit]`.
+    advice: [Column<Advice>; 3],
+    cond_swap: Selector,
+    poseidon: WalletPoseidonConfig,
+}
+
+pub struct MerkleChip {
+    config: MerkleConfig,
+}
+
+impl MerkleChip {
+    pub fn construct(config: MerkleConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fr>,
+        advice: [Column<Advice>; 3],
+        poseidon: WalletPoseidonConfig,
+    ) -> MerkleConfig {
+        for column in &advice {
+            meta.enable_equality(*column);
+        }
+        let cond_swap = meta.selector();
+
+        meta.create_gate("cond_swap", |meta| {
+            let s = meta.query_selector(cond_swap);
+            let current = meta.query_advice(advice[0], Rotation::cur());
+            let sibling = meta.query_advice(advice[1], Rotation::cur());
+            let bit = meta.query_advice(advice[2], Rotation::cur());
+            let left = meta.query_advice(advice[0], Rotation::next());
+            let right = meta.query_advice(advice[1], Rotation::next());
+
+            let one = Expression::Constant(Fr::one());
+            Constraints::with_selector(
+                s,
+                vec![
+                    // bit is boolean
+                    bit.clone() * (one.clone() - bit.clone()),
+                    // left = bit ? sibling : current
+                    left - (bit.clone() * sibling.clone() + (one.clone() - bit.clone()) * current.clone()),
+                    // right = bit ? current : sibling
+                    right - (bit.clone() * current + (one - bit) * sibling),
+                ],
+            )
+        });
+
+        MerkleConfig {
+            advice,
+            cond_swap,
+            poseidon,
+        }
+    }
+
+    /// Conditionally swaps `current`/`sibling` based on `position_bit`
+    /// (`0` = current is the left child, `1` = current is the right child)
+    /// and returns the ordered `(left, right)` pair.
+    fn cond_swap(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        current: AssignedCell<Fr, Fr>,
+        sibling: Value<Fr>,
+        position_bit: Value<bool>,
+    ) -> Result<(AssignedCell<Fr, Fr>, AssignedCell<Fr, Fr>), Error> {
+        layouter.assign_region(
+            || "cond_swap",
+            |mut region| {
+                self.config.cond_swap.enable(&mut region, 0)?;
+
+                current.copy_advice(|| "current", &mut region, self.config.advice[0], 0)?;
+                region.assign_advice(|| "sibling", self.config.advice[1], 0, || sibling)?;
+                region.assign_advice(
+                    || "position_bit",
+                    self.config.advice[2],
+                    0,
+                    || position_bit.map(|b| if b { Fr::one() } else { Fr::zero() }),
+                )?;
+
+                let bit = position_bit;
+                let left = region.assign_advice(
+                    || "left",
+                    self.config.advice[0],
+                    1,
+                    || bit.zip(sibling).zip(current.value().copied()).map(|((b, sib), cur)| {
+                        if b { sib } else { cur }
+                    }),
+                )?;
+                let right = region.assign_advice(
+                    || "right",
+                    self.config.advice[1],
+                    1,
+                    || bit.zip(sibling).zip(current.value().copied()).map(|((b, sib), cur)| {
+                        if b { cur } else { sib }
+                    }),
+                )?;
+
+                Ok((left, right))
+            },
+        )
+    }
+
+    /// Walks `path` (siblings from leaf to root) and `position_bits`,
+    /// folding each level through Poseidon, and returns the resulting root.
+    pub fn root(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        leaf: AssignedCell<Fr, Fr>,
+        path: &[Value<Fr>],
+        position_bits: &[Value<bool>],
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        assert_eq!(path.len(), position_bits.len());
+
+        let poseidon_chip = WalletPoseidonChip::construct(self.config.poseidon.clone());
+        let mut digest = leaf;
+        for (level, (sibling, bit)) in path.iter().zip(position_bits.iter()).enumerate() {
+            let (left, right) = self.cond_swap(
+                layouter.namespace(|| format!("level {level} cond_swap")),
+                digest,
+                *sibling,
+                *bit,
+            )?;
+            digest = poseidon_chip.hash_pair(layouter.namespace(|| format!("level {level} hash")), left, right)?;
+        }
+
+        Ok(digest)
+    }
+}
@@ -0,0 +1,67 @@
+//! Serialization helpers for persisting a [`WalletCirciut`] verifying key,
+//! proving key, and proof bytes (matching halo2's `write`/`read` round-trip),
+//! plus a [`circuit_cost`] report for budgeting `k` before proving.
+//!
+//! w3bstream deployments need to ship the vk to on-chain/off-chain verifiers
+//! and persist the pk between proving runs without re-running `keygen_pk`.
+
+use std::io::{self, Read, Write};
+
+use halo2_curves::bn256::G1;
+use halo2_proofs::{
+    dev::CircuitCost,
+    plonk::{ProvingKey, VerifyingKey},
+    SerdeFormat,
+};
+use halo2_curves::bn256::G1Affine;
+
+use crate::WalletCirciut;
+
+pub fn write_vk(vk: &VerifyingKey<G1Affine>, writer: &mut impl Write) -> io::Result<()> {
+    vk.write(writer, SerdeFormat::RawBytes)
+}
+
+/// `WalletCirciut` sets [`Circuit::Params`](halo2_proofs::plonk::Circuit::Params)
+/// to `()` (it has no runtime parameters). With the `circuit-params` feature
+/// enabled crate-wide, `VerifyingKey::read`/`ProvingKey::read` take that
+/// `Params` value as an extra argument; without it, they don't.
+#[cfg(not(feature = "circuit-params"))]
+pub fn read_vk(reader: &mut impl Read) -> io::Result<VerifyingKey<G1Affine>> {
+    VerifyingKey::read::<_, WalletCirciut>(reader, SerdeFormat::RawBytes)
+}
+
+#[cfg(feature = "circuit-params")]
+pub fn read_vk(reader: &mut impl Read) -> io::Result<VerifyingKey<G1Affine>> {
+    VerifyingKey::read::<_, WalletCirciut>(reader, SerdeFormat::RawBytes, ())
+}
+
+pub fn write_pk(pk: &ProvingKey<G1Affine>, writer: &mut impl Write) -> io::Result<()> {
+    pk.write(writer, SerdeFormat::RawBytes)
+}
+
+#[cfg(not(feature = "circuit-params"))]
+pub fn read_pk(reader: &mut impl Read) -> io::Result<ProvingKey<G1Affine>> {
+    ProvingKey::read::<_, WalletCirciut>(reader, SerdeFormat::RawBytes)
+}
+
+#[cfg(feature = "circuit-params")]
+pub fn read_pk(reader: &mut impl Read) -> io::Result<ProvingKey<G1Affine>> {
+    ProvingKey::read::<_, WalletCirciut>(reader, SerdeFormat::RawBytes, ())
+}
+
+pub fn write_proof(proof: &[u8], writer: &mut impl Write) -> io::Result<()> {
+    writer.write_all(proof)
+}
+
+pub fn read_proof(reader: &mut impl Read) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Reports the number of advice/fixed/instance columns, gates, and estimated
+/// proof size for `WalletCirciut` at the given `k`, so callers can size `k`
+/// before committing to a proving run.
+pub fn circuit_cost(k: u32) -> CircuitCost<G1, WalletCirciut> {
+    CircuitCost::<G1, WalletCirciut>::measure(k, &WalletCirciut::default())
+}
@@ -0,0 +1,59 @@
+//! A thin wrapper around `halo2_gadgets`' Pow5 Poseidon permutation, fixed to
+//! a 3-element state (rate 2) so [`merkle::MerkleChip`](crate::merkle) can
+//! hash sibling pairs and [`WalletCommitmentCirciut`](crate::WalletCommitmentCirciut)
+//! can hash an `(address, blinding)` pair into a single field element.
+
+use halo2_curves::bn256::Fr;
+use halo2_gadgets::poseidon::{
+    primitives::{ConstantLength, P128Pow5T3},
+    Hash, Pow5Chip, Pow5Config,
+};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed},
+};
+
+const WIDTH: usize = 3;
+const RATE: usize = 2;
+
+#[derive(Clone, Debug)]
+pub struct WalletPoseidonConfig {
+    pow5: Pow5Config<Fr, WIDTH, RATE>,
+}
+
+pub struct WalletPoseidonChip {
+    config: WalletPoseidonConfig,
+}
+
+impl WalletPoseidonChip {
+    pub fn construct(config: WalletPoseidonConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fr>,
+        state: [Column<Advice>; WIDTH],
+        partial_sbox: Column<Advice>,
+        rc_a: [Column<Fixed>; WIDTH],
+        rc_b: [Column<Fixed>; WIDTH],
+    ) -> WalletPoseidonConfig {
+        let pow5 = Pow5Chip::configure::<P128Pow5T3>(meta, state, partial_sbox, rc_a, rc_b);
+        WalletPoseidonConfig { pow5 }
+    }
+
+    /// Hashes `(left, right)` with a width-3, rate-2 Poseidon sponge and
+    /// returns the single squeezed output element.
+    pub fn hash_pair(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        left: AssignedCell<Fr, Fr>,
+        right: AssignedCell<Fr, Fr>,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        let chip = Pow5Chip::construct(self.config.pow5.clone());
+        let hasher = Hash::<_, _, P128Pow5T3, ConstantLength<2>, WIDTH, RATE>::init(
+            chip,
+            layouter.namespace(|| "init poseidon"),
+        )?;
+        hasher.hash(layouter.namespace(|| "hash pair"), [left, right])
+    }
+}